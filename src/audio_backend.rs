@@ -0,0 +1,199 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use cpal::{
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    Stream,
+};
+use tokio::sync::mpsc::Sender;
+use whisper_rs::convert_stereo_to_mono_audio;
+
+use crate::resampler::{MonoResampler, ResamplerMode};
+
+pub const AUDIO_BUFFER: usize = 512;
+pub const OUTPUT_SAMPLE_RATE: usize = 16_000;
+pub const RESAMPLE_CHUNK_SIZE: usize = AUDIO_BUFFER;
+
+/// Shared so the main thread can flush the remainder once the stream has
+/// been paused.
+pub type SharedResampleState = Arc<Mutex<(MonoResampler, VecDeque<f32>)>>;
+
+/// cpal delivers variable-length buffers, but `resampler` needs a fixed
+/// chunk size, so samples are buffered here and drained chunk-by-chunk.
+fn audio_input_stream_data_callback(
+    raw_samples: &[f32],
+    channels: usize,
+    tx: &Sender<f32>,
+    resampler: &mut MonoResampler,
+    accumulator: &mut VecDeque<f32>,
+    chunk_size: usize,
+) {
+    // Mono devices need no downmixing.
+    let raw_mono_samples: Vec<f32> = if channels == 1 {
+        raw_samples.to_vec()
+    } else {
+        convert_stereo_to_mono_audio(raw_samples).unwrap()
+    };
+    accumulator.extend(raw_mono_samples);
+
+    while accumulator.len() >= chunk_size {
+        let chunk: Vec<f32> = accumulator.drain(..chunk_size).collect();
+        resampler.process(&chunk).into_iter().for_each(|sample| {
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                // Runs on cpal's own OS thread, so blocking is fine.
+                tx.blocking_send(sample)
+                    .expect("Failed to send audio sample to main thread");
+            }
+            #[cfg(target_arch = "wasm32")]
+            {
+                // The browser's audio-rendering callback has no thread to
+                // park, so drop the sample instead of blocking.
+                let _ = tx.try_send(sample);
+            }
+        });
+    }
+}
+
+/// Flush whatever is left in `accumulator` on stream stop, zero-padded to
+/// a full chunk. Returns the remainder; the caller sends it on afterwards
+/// so the mutex guard never has to be held across an await.
+pub fn flush_audio_accumulator(
+    resampler: &mut MonoResampler,
+    accumulator: &mut VecDeque<f32>,
+    chunk_size: usize,
+) -> Vec<f32> {
+    if accumulator.is_empty() {
+        return Vec::new();
+    }
+
+    let mut chunk: Vec<f32> = accumulator.drain(..).collect();
+    chunk.resize(chunk_size, 0.0);
+
+    resampler.process(&chunk)
+}
+
+/// Create a paused input stream feeding resampled 16 kHz mono samples to
+/// `tx`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn create_paused_audio_stream(
+    tx: Sender<f32>,
+    resampler_mode: ResamplerMode,
+) -> (Stream, SharedResampleState) {
+    // Get the default host and input device
+    let host = cpal::default_host();
+    let input_device = host
+        .default_input_device()
+        .expect("Failed to get default input device");
+    println!("Default input device: {:?}", input_device.name());
+
+    // Configure the input stream with default format
+    // We want to use the default format
+    let supported_config = input_device
+        .supported_input_configs()
+        .expect("No supported input config found")
+        .next()
+        .expect("No supported input config found")
+        .with_max_sample_rate();
+    println!("Input config: {:?}", supported_config);
+
+    let device_sample_rate = supported_config.sample_rate().0 as usize;
+    let channels = supported_config.channels() as usize;
+    let input_config = supported_config.into();
+
+    // Devices report whatever rate they're actually running at (e.g.
+    // 48 kHz), so the resample ratio is derived from that, not assumed.
+    let mono_resampler = MonoResampler::new(
+        resampler_mode,
+        OUTPUT_SAMPLE_RATE as f64 / device_sample_rate as f64,
+        RESAMPLE_CHUNK_SIZE,
+    );
+    let state: SharedResampleState = Arc::new(Mutex::new((mono_resampler, VecDeque::new())));
+    let callback_state = Arc::clone(&state);
+
+    let stream = input_device
+        .build_input_stream(
+            &input_config,
+            move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                let (resampler, accumulator) = &mut *callback_state.lock().unwrap();
+                audio_input_stream_data_callback(
+                    data,
+                    channels,
+                    &tx,
+                    resampler,
+                    accumulator,
+                    RESAMPLE_CHUNK_SIZE,
+                );
+            },
+            move |err| eprintln!("An error occurred on the input audio stream: {}", err),
+            None,
+        )
+        .expect("Failed to build input stream");
+
+    // Initialise with a paused stream
+    stream.pause().expect("Failed to pause stream");
+
+    (stream, state)
+}
+
+/// Same pipeline as the native backend, routed through cpal's WebAudio
+/// (`wasm-bindgen`/`web-sys` `AudioContext`) host instead of a native one.
+/// `default_input_device()` here resolves to the browser's microphone
+/// `MediaStream`; the callback still runs in the browser's own
+/// single-threaded audio-rendering callback, which `audio_input_stream_data_callback`
+/// accounts for in its `#[cfg(target_arch = "wasm32")]` branch.
+#[cfg(target_arch = "wasm32")]
+pub fn create_paused_audio_stream(
+    tx: Sender<f32>,
+    resampler_mode: ResamplerMode,
+) -> (Stream, SharedResampleState) {
+    let host = cpal::default_host();
+    let input_device = host
+        .default_input_device()
+        .expect("Failed to get default input device (browser denied mic access?)");
+
+    let supported_config = input_device
+        .supported_input_configs()
+        .expect("No supported input config found")
+        .next()
+        .expect("No supported input config found")
+        .with_max_sample_rate();
+
+    // Browsers commonly report 48 kHz rather than 44.1 kHz.
+    let device_sample_rate = supported_config.sample_rate().0 as usize;
+    let channels = supported_config.channels() as usize;
+    let input_config = supported_config.into();
+
+    let mono_resampler = MonoResampler::new(
+        resampler_mode,
+        OUTPUT_SAMPLE_RATE as f64 / device_sample_rate as f64,
+        RESAMPLE_CHUNK_SIZE,
+    );
+    let state: SharedResampleState = Arc::new(Mutex::new((mono_resampler, VecDeque::new())));
+    let callback_state = Arc::clone(&state);
+
+    let stream = input_device
+        .build_input_stream(
+            &input_config,
+            move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                let (resampler, accumulator) = &mut *callback_state.lock().unwrap();
+                audio_input_stream_data_callback(
+                    data,
+                    channels,
+                    &tx,
+                    resampler,
+                    accumulator,
+                    RESAMPLE_CHUNK_SIZE,
+                );
+            },
+            move |err| web_sys::console::error_1(&format!("input audio stream error: {err}").into()),
+            None,
+        )
+        .expect("Failed to build input stream");
+
+    stream.pause().expect("Failed to pause stream");
+
+    (stream, state)
+}
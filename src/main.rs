@@ -1,140 +1,161 @@
+mod audio_backend;
+mod resampler;
+mod streaming;
+mod transcript;
+mod vad;
+
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
 use std::{
-    env, process,
-    sync::mpsc::{self, Receiver, SyncSender},
-    time::{Duration, Instant},
+    env,
+    io::{self, Write},
+    process,
 };
 
-use cpal::{
-    traits::{DeviceTrait, HostTrait, StreamTrait},
-    Stream,
+use cpal::{traits::StreamTrait, Stream};
+use tokio::sync::mpsc::{self, Receiver, Sender};
+use whisper_rs::{FullParams, SamplingStrategy, WhisperContext};
+
+use audio_backend::{
+    create_paused_audio_stream, flush_audio_accumulator, SharedResampleState, AUDIO_BUFFER,
+    RESAMPLE_CHUNK_SIZE,
 };
-use rubato::{InterpolationParameters, InterpolationType, Resampler, SincFixedIn, WindowFunction};
-use whisper_rs::{convert_stereo_to_mono_audio, FullParams, SamplingStrategy, WhisperContext};
+use resampler::ResamplerMode;
+use streaming::StreamingTranscriber;
+use transcript::Transcript;
+use vad::{Aggressiveness, FrameDuration, FrameVad};
 
-const VOLUME_THRESHOLD: f32 = 0.05;
 const SILENCE_DURATION: Duration = Duration::from_secs(2);
-const AUDIO_BUFFER: usize = 512;
-const INPUT_SAMPLE_RATE: usize = 44_100;
-const OUTPUT_SAMPLE_RATE: usize = 16_000;
+
+/// Pauses the wrapped stream when dropped. Held across the cancellable
+/// `.await` in [`Stt::record`] so that dropping the `record()` future
+/// part-way through (its caller's `select!` losing, a timeout, etc.)
+/// still stops capturing, instead of leaving the stream running until the
+/// audio thread eventually parks forever on a full channel.
+struct PauseOnDrop<'a> {
+    stream: &'a Stream,
+}
+
+impl Drop for PauseOnDrop<'_> {
+    fn drop(&mut self) {
+        let _ = self.stream.pause();
+    }
+}
 
 struct Stt {
     ctx: WhisperContext,
     audio_data: Vec<f32>,
     audio_receiver: Receiver<f32>,
+    audio_sender: Sender<f32>,
     stream: Stream,
-}
-
-fn audio_input_stream_data_callback(
-    raw_stereo_samples: &[f32],
-    tx: &SyncSender<f32>,
-    resampler: &mut SincFixedIn<f32>,
-) {
-    // Convert stereo to mono
-    let raw_mono_samples: Vec<f32> = convert_stereo_to_mono_audio(raw_stereo_samples).unwrap();
-
-    // Resample the audio to get the target sample rate
-    // TODO: Fix 'Wrong number of frames X in input channel 0, expected Y'
-    let mut mono_samples = resampler
-        .process(&[raw_mono_samples], None)
-        .expect("failed to resample");
-
-    // Send the audio to the main thread
-    mono_samples.pop().unwrap().into_iter().for_each(|sample| {
-        tx.send(sample)
-            .expect("Failed to send audio sample to main thread");
-    });
-}
-
-fn create_paused_audio_stream(tx: SyncSender<f32>) -> Stream {
-    // Get the default host and input device
-    let host = cpal::default_host();
-    let input_device = host
-        .default_input_device()
-        .expect("Failed to get default input device");
-    println!("Default input device: {:?}", input_device.name());
-
-    // Configure the input stream with default format
-    // We want to use the default format
-    let input_config = input_device
-        .supported_input_configs()
-        .expect("No supported input config found")
-        .next()
-        .expect("No supported input config found")
-        .with_max_sample_rate()
-        .into();
-    println!("Input config: {:?}", input_config);
-
-    // Create resampler to convert the audio from the input device's sample rate to 16 kHz
-    let mut mono_resampler = SincFixedIn::<f32>::new(
-        OUTPUT_SAMPLE_RATE as f64 / INPUT_SAMPLE_RATE as f64,
-        2.0,
-        InterpolationParameters {
-            sinc_len: 128,
-            f_cutoff: 0.95,
-            interpolation: InterpolationType::Linear,
-            oversampling_factor: 256,
-            window: WindowFunction::BlackmanHarris2,
-        },
-        AUDIO_BUFFER,
-        1,
-    )
-    .unwrap();
-
-    // Build and play the input stream
-    let stream = input_device
-        .build_input_stream(
-            &input_config,
-            move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                audio_input_stream_data_callback(data, &tx, &mut mono_resampler);
-            },
-            move |err| eprintln!("An error occurred on the input audio stream: {}", err),
-            None,
-        )
-        .expect("Failed to build input stream");
-
-    // Initialise with a paused stream
-    stream.pause().expect("Failed to pause stream");
-
-    stream
+    resample_state: SharedResampleState,
+    vad: FrameVad,
 }
 
 impl Stt {
-    pub fn new(path_to_model: String) -> Self {
+    /// Load the model from a filesystem path. Native targets only — wasm32
+    /// has no filesystem, so it loads the model from bytes instead; see
+    /// [`Stt::new_from_model_bytes`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn new(
+        path_to_model: String,
+        vad_aggressiveness: Aggressiveness,
+        resampler_mode: ResamplerMode,
+    ) -> Self {
         let ctx = WhisperContext::new(&path_to_model).expect("failed to load model");
+        Self::from_context(ctx, vad_aggressiveness, resampler_mode)
+    }
 
-        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 0 });
-        params.set_n_threads(1);
-        params.set_translate(true);
-        params.set_language(Some("en"));
-        params.set_print_special(false);
-        params.set_print_progress(false);
-        params.set_print_realtime(false);
-        params.set_print_timestamps(false);
+    /// Load the model from bytes already fetched by the host JS (e.g. via
+    /// `fetch`), since `wasm32-unknown-unknown` has no filesystem access.
+    #[cfg(target_arch = "wasm32")]
+    pub fn new_from_model_bytes(
+        model_bytes: &[u8],
+        vad_aggressiveness: Aggressiveness,
+        resampler_mode: ResamplerMode,
+    ) -> Self {
+        let ctx =
+            WhisperContext::new_from_buffer(model_bytes).expect("failed to load model");
+        Self::from_context(ctx, vad_aggressiveness, resampler_mode)
+    }
 
-        let (tx, audio_receiver) = mpsc::sync_channel(AUDIO_BUFFER);
+    fn from_context(
+        ctx: WhisperContext,
+        vad_aggressiveness: Aggressiveness,
+        resampler_mode: ResamplerMode,
+    ) -> Self {
+        let (tx, audio_receiver) = mpsc::channel(AUDIO_BUFFER);
+        let audio_sender = tx.clone();
 
         // Create an audio stream
-        let stream = create_paused_audio_stream(tx);
+        let (stream, resample_state) = create_paused_audio_stream(tx, resampler_mode);
 
         Self {
             ctx,
             audio_data: Vec::new(),
             audio_receiver,
+            audio_sender,
             stream,
+            resample_state,
+            vad: FrameVad::new(vad_aggressiveness, FrameDuration::Ms20),
         }
     }
 
+    /// Start a streaming session that re-runs Whisper over a sliding
+    /// window of recent audio, yielding partial transcripts as they
+    /// stabilize instead of waiting for `SILENCE_DURATION` of silence.
+    /// Plays the same `Stream`/`audio_receiver` pair `record()` uses, so
+    /// callers must pick one capture mode at a time.
+    pub fn stream(&mut self) -> StreamingTranscriber {
+        self.stream.play().expect("Failed to start recording");
+        StreamingTranscriber::new(&mut self.ctx, &mut self.audio_receiver)
+    }
+
     /// Record until no voice activity is detected, then output the text.
-    pub fn record(&mut self) -> String {
+    ///
+    /// This is `async` rather than blocking the thread: the cpal callback
+    /// feeds a tokio channel, and awaiting here lets callers `.await`
+    /// segments, select against other futures, or cancel the recording by
+    /// dropping it, instead of spinning on `try_recv`. Cancellation is
+    /// safe: a [`PauseOnDrop`] guard covers the cancellable await so the
+    /// stream is paused however the future ends, and any leftover audio
+    /// from a cancelled attempt is discarded at the top of the next call.
+    pub async fn record(&mut self) -> Transcript {
+        // Discard anything left over from a previous cancelled `record()`:
+        // if that call was dropped mid-`run_voice_activity_detection`, its
+        // partially-filled `audio_data`, unconsumed channel samples, and
+        // the accumulator's in-flight chunk are all still sitting here and
+        // would otherwise get prepended to this recording.
+        self.audio_data.clear();
+        while self.audio_receiver.try_recv().is_ok() {}
+        self.resample_state.lock().unwrap().1.clear();
+
         // Start recording
         println!("Start recording");
         self.stream.play().expect("Failed to start recording");
+        let pause_guard = PauseOnDrop {
+            stream: &self.stream,
+        };
 
         // Get the audio data from the input stream and run voice activity detection
-        self.run_voice_activity_detection();
-
-        // Pause the stream
-        self.stream.pause().expect("Failed to pause stream");
+        self.run_voice_activity_detection().await;
+
+        // Pause the stream, then flush whatever partial chunk is left in the
+        // accumulator so the last few milliseconds of audio aren't dropped.
+        drop(pause_guard);
+        let remainder = {
+            let (resampler, accumulator) = &mut *self.resample_state.lock().unwrap();
+            flush_audio_accumulator(resampler, accumulator, RESAMPLE_CHUNK_SIZE)
+        };
+        for sample in remainder {
+            self.audio_sender
+                .send(sample)
+                .await
+                .expect("Failed to send audio sample to main thread");
+        }
+        while let Ok(sample) = self.audio_receiver.try_recv() {
+            self.audio_data.push(sample);
+        }
 
         // Not sure how we store this value somewhere in the struct
         // without having to initialise it every time
@@ -146,6 +167,8 @@ impl Stt {
         params.set_print_progress(false);
         params.set_print_realtime(false);
         params.set_print_timestamps(false);
+        // Needed for word-level offsets in `Transcript::segments[_].words`.
+        params.set_token_timestamps(true);
 
         // Run the Whisper ASR model
         println!("Run ASR model");
@@ -156,50 +179,75 @@ impl Stt {
         // Clear the audio data
         self.audio_data.clear();
 
-        // Fetch the results
-        let num_segments = self.ctx.full_n_segments();
-
-        (0..num_segments)
-            .map(|i| {
-                self.ctx
-                    .full_get_segment_text(i)
-                    .expect("failed to get segment")
-                    .trim()
-                    .to_string()
-            })
-            .filter(|segment| segment != "[BLANK_AUDIO]")
-            .collect::<Vec<String>>()
-            .join("")
+        // Fetch the results, keeping the segment/word timing Whisper computed
+        Transcript::from_context(&self.ctx)
     }
 
-    /// Simple voice activity detection using silence duration.
+    /// Voice activity detection using frame-based `webrtc_vad`, gating
+    /// `SILENCE_DURATION` on consecutive non-speech frames instead of raw
+    /// sample amplitude.
     ///
-    /// Note that this function will block the main thread,
-    /// while the audio data is being processed concurrently
-    /// through the audio input stream
-    fn run_voice_activity_detection(&mut self) {
-        let mut last_voice_activity = Instant::now();
-        while last_voice_activity.elapsed() < SILENCE_DURATION {
-            if let Ok(sample) = self.audio_receiver.try_recv() {
-                // Check for voice activity
-                if sample.abs() > VOLUME_THRESHOLD {
-                    last_voice_activity = Instant::now();
+    /// Awaits new samples from the cpal callback rather than busy-polling
+    /// `try_recv`, so the task yields to the runtime while recording.
+    async fn run_voice_activity_detection(&mut self) {
+        let mut silence_elapsed_ms: u64 = 0;
+        let silence_duration_ms = SILENCE_DURATION.as_millis() as u64;
+
+        while silence_elapsed_ms < silence_duration_ms {
+            let Some(sample) = self.audio_receiver.recv().await else {
+                break;
+            };
+            self.audio_data.push(sample);
+
+            if let Some(is_voice) = self.vad.push_sample(sample) {
+                if is_voice {
+                    silence_elapsed_ms = 0;
+                } else {
+                    silence_elapsed_ms += self.vad.frame_duration_ms();
                 }
-
-                // Add the sample to the audio_data buffer
-                self.audio_data.push(sample);
             }
         }
     }
 }
 
-fn main() {
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::main]
+async fn main() {
     let Some(model) = env::args().nth(1) else {
         println!("Please provide a path to the model file");
         process::exit(1);
     };
+    let streaming = env::args().nth(2).as_deref() == Some("--stream");
+
+    // Sinc gives the best resample quality and is affordable on a desktop
+    // CPU; `ResamplerMode::Fft` is available for callers that would rather
+    // trade quality for a cheaper resample.
+    let mut stt = Stt::new(model, Aggressiveness::Quality, ResamplerMode::Sinc);
+
+    if streaming {
+        let mut transcriber = stt.stream();
+        while let Some(words) = transcriber.next().await {
+            print!("{words} ");
+            io::stdout().flush().expect("Failed to flush stdout");
+        }
+        println!();
+    } else {
+        let transcript = stt.record().await;
+        println!("{}", transcript.text());
+    }
+}
 
-    let mut stt = Stt::new(model);
-    let text = stt.record();
-    println!("{}", text);
+/// Entry point called from JS: `model_bytes` is the model file fetched by
+/// the browser, since wasm32 has no filesystem to load it from a path.
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+pub async fn record_from_bytes(model_bytes: &[u8]) -> String {
+    let mut stt =
+        Stt::new_from_model_bytes(model_bytes, Aggressiveness::Quality, ResamplerMode::Sinc);
+    stt.record().await.text()
 }
+
+// wasm-bindgen exports `record_from_bytes` directly; this target has no
+// CLI entry point of its own.
+#[cfg(target_arch = "wasm32")]
+fn main() {}
@@ -0,0 +1,152 @@
+use std::sync::Arc;
+
+use realfft::{ComplexToReal, RealFftPlanner, RealToComplex};
+use rubato::{InterpolationParameters, InterpolationType, Resampler, SincFixedIn, WindowFunction};
+
+/// Which resampling algorithm to use when converting the device's native
+/// sample rate down to the 16 kHz Whisper expects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResamplerMode {
+    /// High quality windowed-sinc interpolation (the default).
+    Sinc,
+    /// Cheaper block-by-block FFT resampling, at some cost to quality.
+    Fft,
+}
+
+/// A mono `f32` resampler that is fed fixed-size chunks and hides which
+/// underlying algorithm (sinc or FFT) is doing the work.
+pub enum MonoResampler {
+    Sinc(Box<SincFixedIn<f32>>),
+    Fft(FftResampler),
+}
+
+impl MonoResampler {
+    pub fn new(mode: ResamplerMode, ratio: f64, chunk_size: usize) -> Self {
+        match mode {
+            ResamplerMode::Sinc => Self::Sinc(Box::new(
+                SincFixedIn::<f32>::new(
+                    ratio,
+                    2.0,
+                    InterpolationParameters {
+                        sinc_len: 128,
+                        f_cutoff: 0.95,
+                        interpolation: InterpolationType::Linear,
+                        oversampling_factor: 256,
+                        window: WindowFunction::BlackmanHarris2,
+                    },
+                    chunk_size,
+                    1,
+                )
+                .unwrap(),
+            )),
+            ResamplerMode::Fft => Self::Fft(FftResampler::new(ratio, chunk_size)),
+        }
+    }
+
+    /// Resample exactly `chunk_size` input frames, returning the
+    /// resampled mono samples.
+    pub fn process(&mut self, chunk: &[f32]) -> Vec<f32> {
+        match self {
+            Self::Sinc(resampler) => resampler
+                .process(&[chunk.to_vec()], None)
+                .expect("failed to resample")
+                .pop()
+                .unwrap(),
+            Self::Fft(resampler) => resampler.process(chunk),
+        }
+    }
+}
+
+/// Fixed-block FFT resampler: forward-transform the input block, truncate
+/// or zero-pad the spectrum to the target sample rate, then
+/// inverse-transform back to the time domain. Each block is transformed
+/// on its own; no samples overlap or carry over between blocks.
+pub struct FftResampler {
+    chunk_size: usize,
+    output_size: usize,
+    forward: Arc<dyn RealToComplex<f32>>,
+    inverse: Arc<dyn ComplexToReal<f32>>,
+}
+
+impl FftResampler {
+    pub fn new(ratio: f64, chunk_size: usize) -> Self {
+        let output_size = ((chunk_size as f64) * ratio).round() as usize;
+
+        // Plan once up front rather than per chunk: both `chunk_size` and
+        // `output_size` are fixed for the lifetime of this resampler, and
+        // planning is the expensive part of an FFT call.
+        let mut planner = RealFftPlanner::<f32>::new();
+        let forward = planner.plan_fft_forward(chunk_size);
+        let inverse = planner.plan_fft_inverse(output_size);
+
+        Self {
+            chunk_size,
+            output_size,
+            forward,
+            inverse,
+        }
+    }
+
+    /// Resample exactly `chunk_size` input frames to `output_size` frames.
+    pub fn process(&mut self, chunk: &[f32]) -> Vec<f32> {
+        assert_eq!(chunk.len(), self.chunk_size);
+
+        let mut input = chunk.to_vec();
+        let mut spectrum = self.forward.make_output_vec();
+        self.forward
+            .process(&mut input, &mut spectrum)
+            .expect("forward FFT failed");
+
+        // Rescale the spectrum length to match the target sample rate:
+        // truncate when downsampling, zero-pad when upsampling.
+        let target_bins = self.output_size / 2 + 1;
+        spectrum.resize(target_bins, realfft::num_complex::Complex::new(0.0, 0.0));
+        // The inverse transform rejects a DC bin (or, for an even output
+        // length, a last bin) with a non-zero imaginary part.
+        spectrum[0].im = 0.0;
+        if self.output_size % 2 == 0 {
+            spectrum[target_bins - 1].im = 0.0;
+        }
+
+        let mut output = self.inverse.make_output_vec();
+        self.inverse
+            .process(&mut spectrum, &mut output)
+            .expect("inverse FFT failed");
+
+        // Neither realfft transform is normalized; the forward pass scales
+        // the spectrum by the input length, so divide by that (not the
+        // output length) to keep the amplitude unchanged.
+        let scale = 1.0 / self.chunk_size as f32;
+        output.iter_mut().for_each(|sample| *sample *= scale);
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fft_resampler_output_size_tracks_ratio() {
+        let resampler = FftResampler::new(0.5, 1000);
+        assert_eq!(resampler.output_size, 500);
+    }
+
+    #[test]
+    fn fft_resampler_preserves_dc_amplitude_when_downsampling() {
+        let chunk_size = 512;
+        let ratio = 16_000.0 / 44_100.0;
+        let mut resampler = FftResampler::new(ratio, chunk_size);
+
+        let chunk = vec![0.5_f32; chunk_size];
+        let output = resampler.process(&chunk);
+
+        assert_eq!(output.len(), resampler.output_size);
+        // A constant (DC) signal resampled to a different rate should
+        // still be ~constant at the same amplitude. Before the inverse
+        // FFT normalization fix this came out scaled by chunk_size /
+        // output_size instead of 1.0.
+        let mean = output.iter().sum::<f32>() / output.len() as f32;
+        assert!((mean - 0.5).abs() < 0.05, "mean was {mean}");
+    }
+}
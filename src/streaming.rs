@@ -0,0 +1,184 @@
+use std::collections::VecDeque;
+
+use tokio::sync::mpsc::Receiver;
+use whisper_rs::{FullParams, SamplingStrategy, WhisperContext};
+
+use crate::audio_backend::OUTPUT_SAMPLE_RATE;
+
+const CHUNK_DURATION_MS: usize = 100;
+const CHUNK_SIZE: usize = OUTPUT_SAMPLE_RATE * CHUNK_DURATION_MS / 1_000;
+
+const WINDOW_SECS: usize = 10;
+const STEP_SECS: usize = 2;
+
+/// Keeps the last `WINDOW_SECS` seconds of audio, yielding a fresh window
+/// every time `STEP_SECS` seconds of new audio have accumulated.
+struct SlidingWindow {
+    buffer: VecDeque<f32>,
+    window_samples: usize,
+    step_samples: usize,
+    samples_since_last_window: usize,
+}
+
+impl SlidingWindow {
+    fn new(window_secs: usize, step_secs: usize) -> Self {
+        Self {
+            buffer: VecDeque::new(),
+            window_samples: window_secs * OUTPUT_SAMPLE_RATE,
+            step_samples: step_secs * OUTPUT_SAMPLE_RATE,
+            samples_since_last_window: 0,
+        }
+    }
+
+    /// Push a chunk of resampled audio. Returns the current window once
+    /// enough new audio has arrived to take another step.
+    fn push_chunk(&mut self, chunk: &[f32]) -> Option<Vec<f32>> {
+        self.buffer.extend(chunk);
+        while self.buffer.len() > self.window_samples {
+            self.buffer.pop_front();
+        }
+        self.samples_since_last_window += chunk.len();
+
+        if self.samples_since_last_window < self.step_samples {
+            return None;
+        }
+        self.samples_since_last_window = 0;
+
+        Some(self.buffer.iter().copied().collect())
+    }
+}
+
+/// Returns the words in `current` that are new relative to `previous`,
+/// i.e. everything after their common prefix. Used to only emit the
+/// newly-stabilized part of the transcript on each window re-run.
+fn diff_new_words(previous: &str, current: &str) -> String {
+    let previous_words: Vec<&str> = previous.split_whitespace().collect();
+    let current_words: Vec<&str> = current.split_whitespace().collect();
+
+    let common_len = previous_words
+        .iter()
+        .zip(current_words.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    current_words[common_len..].join(" ")
+}
+
+fn run_full(ctx: &mut WhisperContext, window: &[f32]) -> String {
+    let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 0 });
+    params.set_n_threads(1);
+    params.set_translate(true);
+    params.set_language(Some("en"));
+    params.set_print_special(false);
+    params.set_print_progress(false);
+    params.set_print_realtime(false);
+    params.set_print_timestamps(false);
+
+    ctx.full(params, window).expect("failed to run model");
+
+    (0..ctx.full_n_segments())
+        .map(|i| {
+            ctx.full_get_segment_text(i)
+                .expect("failed to get segment")
+                .trim()
+                .to_string()
+        })
+        .filter(|segment| segment != "[BLANK_AUDIO]")
+        .collect::<Vec<String>>()
+        .join("")
+}
+
+/// Yields partial transcripts by re-running Whisper over a sliding window
+/// as new samples arrive, emitting only the text that stabilized since
+/// the previous window. Reuses `Stt`'s existing capture pipeline rather
+/// than opening a second one.
+pub struct StreamingTranscriber<'a> {
+    ctx: &'a mut WhisperContext,
+    audio_receiver: &'a mut Receiver<f32>,
+    chunk_buffer: Vec<f32>,
+    window: SlidingWindow,
+    previous_text: String,
+}
+
+impl<'a> StreamingTranscriber<'a> {
+    pub fn new(ctx: &'a mut WhisperContext, audio_receiver: &'a mut Receiver<f32>) -> Self {
+        Self {
+            ctx,
+            audio_receiver,
+            chunk_buffer: Vec::with_capacity(CHUNK_SIZE),
+            window: SlidingWindow::new(WINDOW_SECS, STEP_SECS),
+            previous_text: String::new(),
+        }
+    }
+
+    /// Await the next newly-stabilized piece of text. Returns `None` only
+    /// once every sender of the audio channel has been dropped; pausing the
+    /// stream does not end it, and `Stt` holds a sender while it is alive.
+    pub async fn next(&mut self) -> Option<String> {
+        loop {
+            let sample = self.audio_receiver.recv().await?;
+            self.chunk_buffer.push(sample);
+            if self.chunk_buffer.len() < CHUNK_SIZE {
+                continue;
+            }
+            let chunk = std::mem::take(&mut self.chunk_buffer);
+
+            let Some(window) = self.window.push_chunk(&chunk) else {
+                continue;
+            };
+
+            let text = run_full(self.ctx, &window);
+            let new_words = diff_new_words(&self.previous_text, &text);
+            self.previous_text = text;
+
+            if !new_words.is_empty() {
+                return Some(new_words);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_new_words_returns_only_the_new_suffix() {
+        assert_eq!(diff_new_words("hello world", "hello world again"), "again");
+    }
+
+    #[test]
+    fn diff_new_words_returns_everything_when_previous_is_empty() {
+        assert_eq!(diff_new_words("", "hello world"), "hello world");
+    }
+
+    #[test]
+    fn diff_new_words_returns_nothing_when_unchanged() {
+        assert_eq!(diff_new_words("hello world", "hello world"), "");
+    }
+
+    #[test]
+    fn sliding_window_yields_nothing_before_a_full_step() {
+        let mut window = SlidingWindow::new(10, 2);
+        let chunk = vec![0.0; 2 * OUTPUT_SAMPLE_RATE - 1];
+        assert!(window.push_chunk(&chunk).is_none());
+    }
+
+    #[test]
+    fn sliding_window_yields_a_window_once_a_full_step_arrives() {
+        let mut window = SlidingWindow::new(10, 2);
+        let chunk = vec![0.0; 2 * OUTPUT_SAMPLE_RATE];
+        assert!(window.push_chunk(&chunk).is_some());
+    }
+
+    #[test]
+    fn sliding_window_caps_buffer_at_window_size() {
+        let mut window = SlidingWindow::new(1, 1);
+        let window_samples = OUTPUT_SAMPLE_RATE;
+        let chunk = vec![0.0; window_samples * 3];
+        let result = window
+            .push_chunk(&chunk)
+            .expect("a full step's worth of samples was pushed");
+        assert_eq!(result.len(), window_samples);
+    }
+}
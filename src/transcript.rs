@@ -0,0 +1,178 @@
+use std::time::Duration;
+
+use whisper_rs::WhisperContext;
+
+/// Timing and text for a single word, available when token timestamps are
+/// enabled on the `FullParams` used for transcription.
+#[derive(Debug, Clone)]
+pub struct Word {
+    pub text: String,
+    pub start: Duration,
+    pub end: Duration,
+}
+
+/// A transcribed segment with its start/end offsets into the audio, plus
+/// word-level timings when available.
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub text: String,
+    pub start: Duration,
+    pub end: Duration,
+    pub words: Vec<Word>,
+}
+
+/// The full result of a transcription run, carrying the timing
+/// information Whisper computes rather than discarding it.
+#[derive(Debug, Clone, Default)]
+pub struct Transcript {
+    pub segments: Vec<Segment>,
+}
+
+/// whisper.cpp reports segment and token timestamps in centiseconds.
+fn centiseconds_to_duration(centiseconds: i64) -> Duration {
+    Duration::from_millis(centiseconds.max(0) as u64 * 10)
+}
+
+impl Transcript {
+    /// Read segments (and, if present, per-token word timings) out of a
+    /// `WhisperContext` after `full` has been run on it.
+    pub fn from_context(ctx: &WhisperContext) -> Self {
+        let num_segments = ctx.full_n_segments();
+
+        let segments = (0..num_segments)
+            .map(|segment_index| {
+                let text = ctx
+                    .full_get_segment_text(segment_index)
+                    .expect("failed to get segment")
+                    .trim()
+                    .to_string();
+                let start = centiseconds_to_duration(ctx.full_get_segment_t0(segment_index));
+                let end = centiseconds_to_duration(ctx.full_get_segment_t1(segment_index));
+
+                let num_tokens = ctx.full_n_tokens(segment_index);
+                let words = (0..num_tokens)
+                    .filter_map(|token_index| {
+                        let token_text = ctx.full_get_token_text(segment_index, token_index).ok()?;
+                        // Skip whisper.cpp's special tokens, e.g. [_BEG_]/[_TT_123_].
+                        if token_text.starts_with('[') {
+                            return None;
+                        }
+                        let token_data = ctx.full_get_token_data(segment_index, token_index);
+                        Some(Word {
+                            text: token_text.trim().to_string(),
+                            start: centiseconds_to_duration(token_data.t0),
+                            end: centiseconds_to_duration(token_data.t1),
+                        })
+                    })
+                    .collect();
+
+                Segment {
+                    text,
+                    start,
+                    end,
+                    words,
+                }
+            })
+            .filter(|segment| segment.text != "[BLANK_AUDIO]")
+            .collect();
+
+        Self { segments }
+    }
+
+    /// The plain concatenated transcript text, as the original
+    /// string-only `record()` returned.
+    pub fn text(&self) -> String {
+        self.segments
+            .iter()
+            .map(|segment| segment.text.as_str())
+            .collect::<Vec<&str>>()
+            .join("")
+    }
+
+    /// Render as SubRip (`.srt`) subtitles, one cue per segment.
+    pub fn to_srt(&self) -> String {
+        self.segments
+            .iter()
+            .enumerate()
+            .map(|(i, segment)| {
+                format!(
+                    "{}\n{} --> {}\n{}\n",
+                    i + 1,
+                    format_srt_timestamp(segment.start),
+                    format_srt_timestamp(segment.end),
+                    segment.text
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// Render as WebVTT (`.vtt`) subtitles, one cue per segment.
+    pub fn to_vtt(&self) -> String {
+        let mut vtt = String::from("WEBVTT\n\n");
+        let cues = self
+            .segments
+            .iter()
+            .map(|segment| {
+                format!(
+                    "{} --> {}\n{}\n",
+                    format_vtt_timestamp(segment.start),
+                    format_vtt_timestamp(segment.end),
+                    segment.text
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+        vtt.push_str(&cues);
+        vtt
+    }
+}
+
+/// `HH:MM:SS,mmm`, as SRT requires (comma before milliseconds).
+fn format_srt_timestamp(duration: Duration) -> String {
+    format_timestamp(duration, ',')
+}
+
+/// `HH:MM:SS.mmm`, as WebVTT requires (dot before milliseconds).
+fn format_vtt_timestamp(duration: Duration) -> String {
+    format_timestamp(duration, '.')
+}
+
+fn format_timestamp(duration: Duration, fractional_separator: char) -> String {
+    let total_millis = duration.as_millis();
+    let hours = total_millis / 3_600_000;
+    let minutes = (total_millis / 60_000) % 60;
+    let seconds = (total_millis / 1_000) % 60;
+    let millis = total_millis % 1_000;
+
+    format!(
+        "{hours:02}:{minutes:02}:{seconds:02}{fractional_separator}{millis:03}"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn centiseconds_to_duration_converts_to_millis() {
+        assert_eq!(centiseconds_to_duration(150), Duration::from_millis(1_500));
+    }
+
+    #[test]
+    fn centiseconds_to_duration_clamps_negative_values_to_zero() {
+        assert_eq!(centiseconds_to_duration(-10), Duration::from_millis(0));
+    }
+
+    #[test]
+    fn srt_timestamp_uses_a_comma_before_milliseconds() {
+        let duration = Duration::from_millis(3_661_234);
+        assert_eq!(format_srt_timestamp(duration), "01:01:01,234");
+    }
+
+    #[test]
+    fn vtt_timestamp_uses_a_dot_before_milliseconds() {
+        let duration = Duration::from_millis(3_661_234);
+        assert_eq!(format_vtt_timestamp(duration), "01:01:01.234");
+    }
+}
@@ -0,0 +1,82 @@
+use webrtc_vad::{SampleRate, Vad, VadMode};
+
+/// One of the three frame durations `webrtc_vad` accepts at 16 kHz.
+#[derive(Debug, Clone, Copy)]
+pub enum FrameDuration {
+    Ms10,
+    Ms20,
+    Ms30,
+}
+
+impl FrameDuration {
+    fn samples(self) -> usize {
+        match self {
+            Self::Ms10 => 160,
+            Self::Ms20 => 320,
+            Self::Ms30 => 480,
+        }
+    }
+}
+
+/// Mirrors `webrtc_vad::VadMode` so callers don't need that crate in scope.
+#[derive(Debug, Clone, Copy)]
+pub enum Aggressiveness {
+    Quality,
+    LowBitrate,
+    Aggressive,
+    VeryAggressive,
+}
+
+impl From<Aggressiveness> for VadMode {
+    fn from(aggressiveness: Aggressiveness) -> Self {
+        match aggressiveness {
+            Aggressiveness::Quality => VadMode::Quality,
+            Aggressiveness::LowBitrate => VadMode::LowBitrate,
+            Aggressiveness::Aggressive => VadMode::Aggressive,
+            Aggressiveness::VeryAggressive => VadMode::VeryAggressive,
+        }
+    }
+}
+
+/// Buffers 16 kHz mono `f32` samples into frames and classifies each as
+/// speech or silence with `webrtc_vad`.
+pub struct FrameVad {
+    vad: Vad,
+    frame_buffer: Vec<i16>,
+    frame_size: usize,
+}
+
+impl FrameVad {
+    pub fn new(aggressiveness: Aggressiveness, frame_duration: FrameDuration) -> Self {
+        let frame_size = frame_duration.samples();
+        Self {
+            vad: Vad::new_with_rate_and_mode(SampleRate::Rate16kHz, aggressiveness.into()),
+            frame_buffer: Vec::with_capacity(frame_size),
+            frame_size,
+        }
+    }
+
+    /// Push one resampled sample. Returns `Some(is_voice)` once a full
+    /// frame has accumulated, `None` while the frame is still filling up.
+    pub fn push_sample(&mut self, sample: f32) -> Option<bool> {
+        self.frame_buffer.push((sample * i16::MAX as f32) as i16);
+
+        if self.frame_buffer.len() < self.frame_size {
+            return None;
+        }
+
+        let is_voice = self
+            .vad
+            .is_voice_segment(&self.frame_buffer)
+            .expect("webrtc_vad frame size mismatch");
+        self.frame_buffer.clear();
+
+        Some(is_voice)
+    }
+
+    /// Duration covered by a single frame, used to convert a run of
+    /// consecutive silent frames into a wall-clock silence duration.
+    pub fn frame_duration_ms(&self) -> u64 {
+        (self.frame_size as u64 * 1000) / 16_000
+    }
+}